@@ -0,0 +1,23 @@
+use turbo_tasks::primitives::U64Vc;
+use turbo_tasks_fs::FileSystemPathVc;
+
+/// Configures how chunks for a build are placed and named in the output
+/// filesystem.
+///
+/// Only the members this crate snapshot actually needs are declared here;
+/// `ChunkItem`/`ChunkVc`/`ChunkGroupVc`/`ChunkableAsset`/etc. live alongside
+/// this trait in the full `chunk` module and are left untouched.
+#[turbo_tasks::value_trait]
+pub trait ChunkingContext {
+    /// Resolves the root-relative output path for an asset, given the
+    /// content-addressed name (e.g. a `{content_hash}.{ext}` string) it
+    /// should be published under.
+    fn asset_path(&self, content_hash: &str) -> FileSystemPathVc;
+
+    /// The largest a static asset's content may be, in bytes, and still be
+    /// inlined as a `data:` URI instead of written to the output folder.
+    /// `0` (the default) means assets are never inlined.
+    fn asset_inline_limit(&self) -> U64Vc {
+        U64Vc::cell(0)
+    }
+}
@@ -0,0 +1,84 @@
+use std::{fmt, ops::Deref, sync::Arc};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use turbo_tasks::trace::{TraceRawVcs, TraceRawVcsContext};
+
+/// A cheaply-clonable, immutable string backed by an `Arc<str>`.
+///
+/// Intended for struct fields that get cloned on every turbo_tasks
+/// invocation (chunk paths, template strings, ...): cloning an `RcStr` only
+/// bumps a reference count instead of copying the backing bytes.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RcStr(Arc<str>);
+
+impl RcStr {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for RcStr {
+    fn default() -> Self {
+        RcStr(Arc::from(""))
+    }
+}
+
+impl Deref for RcStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for RcStr {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for RcStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for RcStr {
+    fn from(s: &str) -> Self {
+        RcStr(Arc::from(s))
+    }
+}
+
+impl From<String> for RcStr {
+    fn from(s: String) -> Self {
+        RcStr(Arc::from(s))
+    }
+}
+
+// Hand-rolled rather than derived: deriving would require serde's optional
+// `rc` feature to serialize the `Arc<str>` field directly, which isn't worth
+// depending on just for this. Serializing through `str` keeps the wire
+// format a plain JSON string.
+impl Serialize for RcStr {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for RcStr {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(RcStr::from)
+    }
+}
+
+// An `RcStr` never holds a `Vc`, so there is nothing for the GC to trace
+// through it.
+impl TraceRawVcs for RcStr {
+    fn trace_raw_vcs(&self, _trace_context: &mut TraceRawVcsContext) {}
+}
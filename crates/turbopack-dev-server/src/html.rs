@@ -1,17 +1,46 @@
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use anyhow::{anyhow, Result};
 use mime_guess::mime::TEXT_HTML_UTF_8;
-use turbo_tasks::{debug::ValueDebug, primitives::StringVc, ValueToString};
-use turbo_tasks_fs::{embed_file, File, FileContent, FileContentVc, FileSystemPathVc};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{primitives::StringVc, ValueToString};
+use turbo_tasks_fs::{embed_file, File, FileContent, FileContentVc, FileSystemPath, FileSystemPathVc};
 use turbopack_core::{
     asset::{Asset, AssetVc},
-    chunk::{ChunkGroupVc, ChunkReferenceVc},
-    reference::{AssetReferencesVc, SingleAssetReferenceVc},
-    version::{Update, UpdateVc, Version, VersionVc, VersionedContent, VersionedContentVc},
+    chunk::{ChunkGroupVc, ChunkReferenceVc, ChunkVc},
+    rcstr::RcStr,
+    reference::{AssetReference, AssetReferencesVc, SingleAssetReferenceVc},
+    version::{
+        PartialUpdate, Update, UpdateVc, Version, VersionVc, VersionedContent, VersionedContentVc,
+    },
 };
 use turbopack_hash::{encode_hex, Xxh3Hash64Hasher};
 
+/// User-customizable parts of the generated document: page metadata and
+/// markup injected around the generated chunk `<script>`/`<link>` tags.
+///
+/// The chunk tags themselves are always injected at their correct position
+/// (stylesheets/head-only tags in `<head>`, scripts in `<body>`) regardless
+/// of what's configured here.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DevHtmlAssetTemplate {
+    /// Rendered as `<title>`, if set.
+    pub title: Option<RcStr>,
+    /// Rendered as the `<html lang="...">` attribute, if set.
+    pub lang: Option<RcStr>,
+    /// The id of the root element. Defaults to `"root"`.
+    pub root_id: Option<RcStr>,
+    /// Arbitrary `<meta>`/`<link>` markup appended to `<head>`.
+    pub head_tags: Vec<RcStr>,
+    /// Markup inserted right before the root element.
+    pub pre_body: Option<RcStr>,
+    /// Markup inserted right after the chunk `<script>` tags.
+    pub post_body: Option<RcStr>,
+}
+
 /// The HTML entry point of the dev server.
 ///
 /// Generates an HTML page that includes the ES and CSS chunks.
@@ -19,6 +48,7 @@ use turbopack_hash::{encode_hex, Xxh3Hash64Hasher};
 pub struct DevHtmlAsset {
     path: FileSystemPathVc,
     chunk_groups: Vec<ChunkGroupVc>,
+    template: DevHtmlAssetTemplate,
 }
 
 #[turbo_tasks::value_impl]
@@ -98,9 +128,27 @@ impl HtmlRuntimeAssetVc {
 }
 
 impl DevHtmlAsset {
-    /// Create a new dev HTML asset.
+    /// Create a new dev HTML asset with the default template (no title, no
+    /// extra head/body markup, root element id `"root"`).
     pub fn new(path: FileSystemPathVc, chunk_groups: Vec<ChunkGroupVc>) -> Self {
-        DevHtmlAsset { path, chunk_groups }
+        DevHtmlAsset {
+            path,
+            chunk_groups,
+            template: DevHtmlAssetTemplate::default(),
+        }
+    }
+
+    /// Create a new dev HTML asset with a custom [`DevHtmlAssetTemplate`].
+    pub fn new_with_template(
+        path: FileSystemPathVc,
+        chunk_groups: Vec<ChunkGroupVc>,
+        template: DevHtmlAssetTemplate,
+    ) -> Self {
+        DevHtmlAsset {
+            path,
+            chunk_groups,
+            template,
+        }
     }
 }
 
@@ -111,37 +159,124 @@ impl DevHtmlAssetVc {
         let this = self.await?;
         let context_path = this.path.parent().await?;
 
-        let mut chunk_paths = vec![];
-        for chunk_group in &this.chunk_groups {
-            for chunk in chunk_group.chunks().await?.iter() {
-                let chunk_id = chunk.path().to_string().await?;
-                let chunk_path = &*chunk.path().await?;
-                if let Some(relative_path) = context_path.get_relative_path_to(chunk_path) {
-                    chunk_paths.push((relative_path, chunk_id.clone()));
+        let chunk_paths = chunk_paths_in_dependency_order(&context_path, &this.chunk_groups).await?;
+
+        let html_runtime_reference = &*self.html_runtime_reference().asset().path().await?;
+        let html_runtime_path: RcStr = context_path
+            .get_relative_path_to(html_runtime_reference)
+            .ok_or_else(|| anyhow!("html runtime path is not relative to context path"))?
+            .into();
+
+        Ok(DevHtmlAssetContent::new(chunk_paths, html_runtime_path, this.template.clone()).cell())
+    }
+}
+
+/// Collects the chunks of `chunk_groups` relative to `context_path`, ordered
+/// so that a chunk's dependencies (found via its `AssetReferences`) always
+/// come before the chunk itself. This keeps CSS cascade order and initial
+/// script execution order reproducible across runs instead of depending on
+/// whatever order `chunk_group.chunks()` happens to yield.
+///
+/// Chunks with no dependency relationship between them are ordered by their
+/// relative path as a stable tie-break.
+async fn chunk_paths_in_dependency_order(
+    context_path: &FileSystemPath,
+    chunk_groups: &[ChunkGroupVc],
+) -> Result<Vec<(RcStr, RcStr)>> {
+    let mut relative_paths = HashMap::new();
+    let mut dependencies: HashMap<RcStr, Vec<RcStr>> = HashMap::new();
+
+    for chunk_group in chunk_groups {
+        for chunk in chunk_group.chunks().await?.iter() {
+            let chunk_id: RcStr = chunk.path().to_string().await?.as_str().into();
+            let chunk_path = &*chunk.path().await?;
+            let Some(relative_path) = context_path.get_relative_path_to(chunk_path) else {
+                continue;
+            };
+            let relative_path: RcStr = relative_path.into();
+            relative_paths.insert(chunk_id.clone(), relative_path);
+
+            // `chunk.references()` mixes references to sibling chunks (what we need
+            // for ordering) with references to other assets entirely, e.g. a CSS
+            // chunk's embedded images. Resolving each referenced asset down to a
+            // `ChunkVc` specifically is what tells those apart: only a reference
+            // that's actually another chunk can ever match a `chunk_id` below, so
+            // this can't silently degrade into comparing unrelated path domains.
+            let mut deps = Vec::new();
+            for reference in chunk.references().await?.iter() {
+                for asset in reference.resolve_reference().await?.primary_assets()?.iter() {
+                    let Some(dep_chunk) = ChunkVc::resolve_from(asset).await? else {
+                        continue;
+                    };
+                    let dep_id: RcStr = dep_chunk.path().to_string().await?.as_str().into();
+                    if dep_id != chunk_id {
+                        deps.push(dep_id);
+                    }
                 }
             }
+            dependencies.insert(chunk_id, deps);
         }
+    }
 
-        let html_runtime_reference = &*self.html_runtime_reference().asset().path().await?;
-        let html_runtime_path = context_path
-            .get_relative_path_to(html_runtime_reference)
-            .ok_or_else(|| anyhow!("html runtime path is not relative to context path"))?;
+    let mut chunk_ids: Vec<&RcStr> = relative_paths.keys().collect();
+    chunk_ids.sort_by_key(|chunk_id| &relative_paths[*chunk_id]);
+
+    let mut visited = HashSet::new();
+    let mut ordered = Vec::new();
+    for chunk_id in chunk_ids {
+        visit_chunk_in_dependency_order(
+            chunk_id,
+            &relative_paths,
+            &dependencies,
+            &mut visited,
+            &mut ordered,
+        );
+    }
+
+    Ok(ordered)
+}
+
+fn visit_chunk_in_dependency_order(
+    chunk_id: &RcStr,
+    relative_paths: &HashMap<RcStr, RcStr>,
+    dependencies: &HashMap<RcStr, Vec<RcStr>>,
+    visited: &mut HashSet<RcStr>,
+    ordered: &mut Vec<(RcStr, RcStr)>,
+) {
+    if !visited.insert(chunk_id.clone()) {
+        return;
+    }
+
+    if let Some(deps) = dependencies.get(chunk_id) {
+        let mut deps: Vec<&RcStr> = deps.iter().filter(|dep| relative_paths.contains_key(*dep)).collect();
+        deps.sort_by_key(|dep| &relative_paths[*dep]);
+        for dep in deps {
+            visit_chunk_in_dependency_order(dep, relative_paths, dependencies, visited, ordered);
+        }
+    }
 
-        Ok(DevHtmlAssetContent::new(chunk_paths, html_runtime_path).cell())
+    if let Some(relative_path) = relative_paths.get(chunk_id) {
+        ordered.push((relative_path.clone(), chunk_id.clone()));
     }
 }
 
 #[turbo_tasks::value]
 struct DevHtmlAssetContent {
-    chunk_paths: Arc<Vec<(String, String)>>,
-    html_runtime_path: String,
+    chunk_paths: Arc<Vec<(RcStr, RcStr)>>,
+    html_runtime_path: RcStr,
+    template: Arc<DevHtmlAssetTemplate>,
 }
 
 impl DevHtmlAssetContent {
-    pub fn new(chunk_paths: Vec<(String, String)>, html_runtime_path: String) -> Self {
+    pub fn new(
+        chunk_paths: Vec<(RcStr, RcStr)>,
+        html_runtime_path: RcStr,
+        template: DevHtmlAssetTemplate,
+    ) -> Self {
         DevHtmlAssetContent {
             chunk_paths: Arc::new(chunk_paths),
             html_runtime_path,
+            template: Arc::new(template),
         }
     }
 }
@@ -163,7 +298,10 @@ impl DevHtmlAssetContentVc {
 
         for (relative_path, chunk_id) in &*this.chunk_paths {
             if relative_path.ends_with(".js") {
-                scripts.push(format!("<script src=\"{}\"></script>", relative_path));
+                scripts.push(format!(
+                    "<script data-turbopack-chunk-id=\"{}\" src=\"{}\"></script>",
+                    chunk_id, relative_path
+                ));
             } else if relative_path.ends_with(".css") {
                 stylesheets.push(format!(
                     "<link data-turbopack-chunk-id=\"{}\" rel=\"stylesheet\" href=\"{}\">",
@@ -174,11 +312,34 @@ impl DevHtmlAssetContentVc {
             }
         }
 
+        let template = &*this.template;
+        let lang_attr = template
+            .lang
+            .as_deref()
+            .map(|lang| format!(" lang=\"{}\"", lang))
+            .unwrap_or_default();
+        let title_tag = template
+            .title
+            .as_deref()
+            .map(|title| format!("<title>{}</title>\n", title))
+            .unwrap_or_default();
+        let head_tags = template.head_tags.join("\n");
+        let root_id = template.root_id.as_deref().unwrap_or("root");
+        let pre_body = template.pre_body.as_deref().unwrap_or("");
+        let post_body = template.post_body.as_deref().unwrap_or("");
+
         let html = format!(
-            "<!DOCTYPE html>\n<html>\n<head>\n{}\n</head>\n<body>\n<div \
-             id=root></div>\n{}\n</body>\n</html>",
-            stylesheets.join("\n"),
-            scripts.join("\n"),
+            "<!DOCTYPE html>\n<html{lang_attr}>\n<head>\n{title_tag}{head_tags}\n{stylesheets}\n\
+             </head>\n<body>\n{pre_body}\n<div id=\"{root_id}\"></div>\n{scripts}\n{post_body}\n\
+             </body>\n</html>",
+            lang_attr = lang_attr,
+            title_tag = title_tag,
+            head_tags = head_tags,
+            stylesheets = stylesheets.join("\n"),
+            pre_body = pre_body,
+            root_id = root_id,
+            scripts = scripts.join("\n"),
+            post_body = post_body,
         );
 
         Ok(FileContent::Content(File::from_source(html).with_content_type(TEXT_HTML_UTF_8)).into())
@@ -189,6 +350,7 @@ impl DevHtmlAssetContentVc {
         let this = self.await?;
         Ok(DevHtmlAssetVersion {
             chunk_paths: Arc::clone(&this.chunk_paths),
+            template: Arc::clone(&this.template),
         }
         .cell())
     }
@@ -216,22 +378,175 @@ impl VersionedContent for DevHtmlAssetContent {
         let to = to_version.await?;
         let from = from_version.await?;
 
-        if to.chunk_paths == from.chunk_paths {
+        if to.chunk_paths == from.chunk_paths && to.template == from.template {
             return Ok(Update::None.into());
         }
 
-        Err(anyhow!(
-            "cannot update `DevHtmlAssetContentVc` from version {:?} to version {:?}: the \
-             versions contain different chunks, which is not yet supported",
-            from_version.dbg().await?,
-            to_version.dbg().await?,
-        ))
+        if to.template != from.template {
+            // There's no client-side mechanism to patch arbitrary head/body
+            // markup in place, so a template edit always needs a full reload.
+            return Err(anyhow!(
+                "cannot update `DevHtmlAssetContentVc`: the template changed, which requires a \
+                 full reload"
+            ));
+        }
+
+        // Diff the two chunk path sets by `data-turbopack-chunk-id` (the chunk's
+        // path-derived id) so the client can patch the `<script>`/`<link>` tags in
+        // place instead of reloading the whole page. The html runtime's own
+        // `<script>` tag is never part of `chunk_paths`, so it's never touched here.
+        let instruction = diff_chunk_paths(&from.chunk_paths, &to.chunk_paths)?;
+
+        Ok(Update::Partial(PartialUpdate {
+            to: to_version.into(),
+            instruction: StringVc::cell(serde_json::to_string(&instruction)?),
+        })
+        .into())
+    }
+}
+
+/// Computes the `added`/`removed`/`changed` tags between two renders of
+/// [`DevHtmlAssetContent::content`], keyed by `data-turbopack-chunk-id`.
+///
+/// Each `added`/`changed` entry also records the chunk id it should be
+/// inserted immediately after (`None` for "first of its kind"), preserving
+/// `to_chunk_paths`'s dependency order (see `chunk_paths_in_dependency_order`)
+/// among scripts and among stylesheets — the two DOM regions the html
+/// runtime inserts them into.
+fn diff_chunk_paths(
+    from_chunk_paths: &[(RcStr, RcStr)],
+    to_chunk_paths: &[(RcStr, RcStr)],
+) -> Result<HtmlUpdateInstruction> {
+    let from_chunks: HashMap<RcStr, RcStr> = from_chunk_paths
+        .iter()
+        .map(|(path, id)| (id.clone(), path.clone()))
+        .collect();
+    let to_chunks: HashMap<RcStr, RcStr> = to_chunk_paths
+        .iter()
+        .map(|(path, id)| (id.clone(), path.clone()))
+        .collect();
+
+    let to_scripts: Vec<RcStr> = to_chunk_paths
+        .iter()
+        .filter(|(path, _)| path.ends_with(".js"))
+        .map(|(_, id)| id.clone())
+        .collect();
+    let to_stylesheets: Vec<RcStr> = to_chunk_paths
+        .iter()
+        .filter(|(path, _)| path.ends_with(".css"))
+        .map(|(_, id)| id.clone())
+        .collect();
+
+    let after = |asset_type: &HtmlAssetType, chunk_id: &RcStr| -> Option<RcStr> {
+        let order = match asset_type {
+            HtmlAssetType::Script => &to_scripts,
+            HtmlAssetType::Stylesheet => &to_stylesheets,
+        };
+        let index = order.iter().position(|id| id == chunk_id)?;
+        (index > 0).then(|| order[index - 1].clone())
+    };
+
+    // Walk `to_chunk_paths` in its own dependency order, so that the html
+    // runtime, which applies entries in payload order, finds each entry's
+    // `after` chunk already in place.
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+    for (relative_path, chunk_id) in to_chunk_paths {
+        let asset_type = HtmlAssetType::from_relative_path(relative_path)?;
+        match from_chunks.get(chunk_id) {
+            None => added.push(HtmlChunkUpdate::new(
+                chunk_id,
+                relative_path,
+                after(&asset_type, chunk_id),
+            )?),
+            Some(from_path) if from_path != relative_path => {
+                changed.push(HtmlChunkUpdate::new(
+                    chunk_id,
+                    relative_path,
+                    after(&asset_type, chunk_id),
+                )?)
+            }
+            _ => {}
+        }
+    }
+
+    let mut removed = Vec::new();
+    for (chunk_id, relative_path) in &from_chunks {
+        if !to_chunks.contains_key(chunk_id) {
+            removed.push(HtmlChunkUpdate::new(chunk_id, relative_path, None)?);
+        }
     }
+
+    // Keep the instruction payload deterministic regardless of hash map
+    // iteration order.
+    removed.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+
+    Ok(HtmlUpdateInstruction {
+        added,
+        removed,
+        changed,
+    })
+}
+
+/// A single `<script>`/`<link>` tag mutation, keyed by the chunk's
+/// `data-turbopack-chunk-id`, that the html runtime can apply to the DOM.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HtmlChunkUpdate {
+    chunk_id: RcStr,
+    relative_path: RcStr,
+    asset_type: HtmlAssetType,
+    /// The chunk id this tag should be inserted immediately after within its
+    /// `asset_type`'s DOM region (`None` for "first of its kind"), so the
+    /// html runtime can preserve the dependency order computed by
+    /// `chunk_paths_in_dependency_order` instead of always appending.
+    after: Option<RcStr>,
+}
+
+impl HtmlChunkUpdate {
+    fn new(chunk_id: &RcStr, relative_path: &RcStr, after: Option<RcStr>) -> Result<Self> {
+        Ok(Self {
+            chunk_id: chunk_id.clone(),
+            relative_path: relative_path.clone(),
+            asset_type: HtmlAssetType::from_relative_path(relative_path)?,
+            after,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum HtmlAssetType {
+    Script,
+    Stylesheet,
+}
+
+impl HtmlAssetType {
+    fn from_relative_path(relative_path: &str) -> Result<Self> {
+        if relative_path.ends_with(".js") {
+            Ok(HtmlAssetType::Script)
+        } else if relative_path.ends_with(".css") {
+            Ok(HtmlAssetType::Stylesheet)
+        } else {
+            Err(anyhow!("chunk with unknown asset type: {}", relative_path))
+        }
+    }
+}
+
+/// The JSON payload sent to `html-runtime.js` describing which `<script>`/
+/// `<link>` tags to inject, remove, or point at a new hashed path.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HtmlUpdateInstruction {
+    added: Vec<HtmlChunkUpdate>,
+    removed: Vec<HtmlChunkUpdate>,
+    changed: Vec<HtmlChunkUpdate>,
 }
 
 #[turbo_tasks::value]
 struct DevHtmlAssetVersion {
-    chunk_paths: Arc<Vec<(String, String)>>,
+    chunk_paths: Arc<Vec<(RcStr, RcStr)>>,
+    template: Arc<DevHtmlAssetTemplate>,
 }
 
 #[turbo_tasks::value_impl]
@@ -243,8 +558,399 @@ impl Version for DevHtmlAssetVersion {
             hasher.write(relative_path.as_bytes());
             hasher.write(chunk_id.as_bytes());
         }
+
+        hasher.write(self.template.title.as_deref().unwrap_or("").as_bytes());
+        hasher.write(self.template.lang.as_deref().unwrap_or("").as_bytes());
+        hasher.write(self.template.root_id.as_deref().unwrap_or("").as_bytes());
+        for head_tag in &self.template.head_tags {
+            hasher.write(head_tag.as_bytes());
+        }
+        hasher.write(self.template.pre_body.as_deref().unwrap_or("").as_bytes());
+        hasher.write(self.template.post_body.as_deref().unwrap_or("").as_bytes());
+
         let hash = hasher.finish();
         let hex_hash = encode_hex(hash);
         Ok(StringVc::cell(hex_hash))
     }
 }
+
+/// The production counterpart of [`DevHtmlAsset`].
+///
+/// Generates a static, cacheable HTML document for the same `chunk_groups`:
+/// no `html-runtime.js` reference, entry scripts marked `type="module"`, the
+/// remaining JS chunks of each group emitted as `<link rel="modulepreload">`,
+/// and CSS chunks emitted as ordered `<link rel="stylesheet">`.
+#[turbo_tasks::value(shared)]
+pub struct BuildHtmlAsset {
+    path: FileSystemPathVc,
+    chunk_groups: Vec<ChunkGroupVc>,
+}
+
+impl BuildHtmlAsset {
+    /// Create a new production HTML asset.
+    pub fn new(path: FileSystemPathVc, chunk_groups: Vec<ChunkGroupVc>) -> Self {
+        BuildHtmlAsset { path, chunk_groups }
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for BuildHtmlAsset {
+    #[turbo_tasks::function]
+    fn path(&self) -> FileSystemPathVc {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn content(self_vc: BuildHtmlAssetVc) -> FileContentVc {
+        self_vc.html_content().content()
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self_vc: BuildHtmlAssetVc) -> Result<AssetReferencesVc> {
+        let this = self_vc.await?;
+        let mut references = Vec::new();
+        for chunk_group in &this.chunk_groups {
+            let chunks = chunk_group.chunks().await?;
+            for chunk in chunks.iter() {
+                references.push(ChunkReferenceVc::new(*chunk).into());
+            }
+        }
+        Ok(AssetReferencesVc::cell(references))
+    }
+
+    #[turbo_tasks::function]
+    fn versioned_content(self_vc: BuildHtmlAssetVc) -> VersionedContentVc {
+        self_vc.html_content().into()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl BuildHtmlAssetVc {
+    #[turbo_tasks::function]
+    async fn html_content(self) -> Result<BuildHtmlAssetContentVc> {
+        let this = self.await?;
+        let context_path = this.path.parent().await?;
+
+        let mut chunks = vec![];
+        for chunk_group in &this.chunk_groups {
+            let mut group_chunks = Vec::new();
+            for chunk in chunk_group.chunks().await?.iter() {
+                let chunk_id = chunk.path().to_string().await?;
+                let chunk_path = &*chunk.path().await?;
+                let Some(relative_path) = context_path.get_relative_path_to(chunk_path) else {
+                    continue;
+                };
+                group_chunks.push((relative_path, (*chunk_id).clone()));
+            }
+
+            // The entry script is whichever chunk in the group is actually JS, not
+            // whatever `chunks()` happens to yield first: that order isn't guaranteed,
+            // and a CSS chunk in the first slot would otherwise leave nothing to
+            // execute the entry module.
+            let entry_index = group_chunks
+                .iter()
+                .position(|(relative_path, _)| relative_path.ends_with(".js"));
+
+            for (i, (relative_path, chunk_id)) in group_chunks.into_iter().enumerate() {
+                let kind = if relative_path.ends_with(".css") {
+                    BuildChunkKind::Stylesheet
+                } else if Some(i) == entry_index {
+                    BuildChunkKind::EntryScript
+                } else {
+                    BuildChunkKind::ModulePreload
+                };
+                chunks.push((relative_path, chunk_id, kind));
+            }
+        }
+
+        Ok(BuildHtmlAssetContent::new(chunks).cell())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BuildChunkKind {
+    EntryScript,
+    ModulePreload,
+    Stylesheet,
+}
+
+#[turbo_tasks::value]
+struct BuildHtmlAssetContent {
+    chunks: Arc<Vec<(String, String, BuildChunkKind)>>,
+}
+
+impl BuildHtmlAssetContent {
+    pub fn new(chunks: Vec<(String, String, BuildChunkKind)>) -> Self {
+        BuildHtmlAssetContent {
+            chunks: Arc::new(chunks),
+        }
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl BuildHtmlAssetContentVc {
+    #[turbo_tasks::function]
+    async fn content(self) -> Result<FileContentVc> {
+        let this = self.await?;
+
+        let mut head = Vec::new();
+        let mut entry_scripts = Vec::new();
+
+        for (relative_path, _chunk_id, kind) in &*this.chunks {
+            match kind {
+                BuildChunkKind::Stylesheet => {
+                    head.push(format!(
+                        "<link rel=\"stylesheet\" href=\"{}\">",
+                        relative_path
+                    ));
+                }
+                BuildChunkKind::ModulePreload => {
+                    head.push(format!(
+                        "<link rel=\"modulepreload\" href=\"{}\">",
+                        relative_path
+                    ));
+                }
+                BuildChunkKind::EntryScript => {
+                    entry_scripts.push(format!(
+                        "<script type=\"module\" src=\"{}\"></script>",
+                        relative_path
+                    ));
+                }
+            }
+        }
+
+        let html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n{}\n</head>\n<body>\n<div \
+             id=root></div>\n{}\n</body>\n</html>",
+            head.join("\n"),
+            entry_scripts.join("\n"),
+        );
+
+        Ok(FileContent::Content(File::from_source(html).with_content_type(TEXT_HTML_UTF_8)).into())
+    }
+
+    #[turbo_tasks::function]
+    async fn version(self) -> Result<BuildHtmlAssetVersionVc> {
+        let this = self.await?;
+        Ok(BuildHtmlAssetVersion {
+            chunks: Arc::clone(&this.chunks),
+        }
+        .cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl VersionedContent for BuildHtmlAssetContent {
+    #[turbo_tasks::function]
+    fn content(self_vc: BuildHtmlAssetContentVc) -> FileContentVc {
+        self_vc.content()
+    }
+
+    #[turbo_tasks::function]
+    fn version(self_vc: BuildHtmlAssetContentVc) -> VersionVc {
+        self_vc.version().into()
+    }
+
+    #[turbo_tasks::function]
+    async fn update(_self_vc: BuildHtmlAssetContentVc, _from_version: VersionVc) -> Result<UpdateVc> {
+        // Production output is static: there is no running html runtime to patch
+        // in place, so every change is a fresh page load.
+        Err(anyhow!(
+            "`BuildHtmlAssetContent` does not support incremental updates"
+        ))
+    }
+}
+
+#[turbo_tasks::value]
+struct BuildHtmlAssetVersion {
+    chunks: Arc<Vec<(String, String, BuildChunkKind)>>,
+}
+
+#[turbo_tasks::value_impl]
+impl Version for BuildHtmlAssetVersion {
+    #[turbo_tasks::function]
+    async fn id(&self) -> Result<StringVc> {
+        let mut hasher = Xxh3Hash64Hasher::new();
+        for (relative_path, chunk_id, kind) in &*self.chunks {
+            hasher.write(relative_path.as_bytes());
+            hasher.write(chunk_id.as_bytes());
+            hasher.write(&[*kind as u8]);
+        }
+        let hash = hasher.finish();
+        let hex_hash = encode_hex(hash);
+        Ok(StringVc::cell(hex_hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(relative_path: &str, chunk_id: &str) -> (RcStr, RcStr) {
+        (relative_path.into(), chunk_id.into())
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed() {
+        let from = vec![entry("a.js", "a"), entry("b.js", "b")];
+        // a unchanged, b's hashed path changed, c is new.
+        let to = vec![entry("a.js", "a"), entry("b2.js", "b"), entry("c.js", "c")];
+
+        let instruction = diff_chunk_paths(&from, &to).unwrap();
+
+        assert_eq!(instruction.added.len(), 1);
+        assert_eq!(instruction.added[0].chunk_id, RcStr::from("c"));
+        assert_eq!(instruction.changed.len(), 1);
+        assert_eq!(instruction.changed[0].chunk_id, RcStr::from("b"));
+        assert_eq!(instruction.removed.len(), 0);
+    }
+
+    #[test]
+    fn diff_reports_removed_chunks() {
+        let from = vec![entry("a.js", "a"), entry("b.js", "b")];
+        let to = vec![entry("a.js", "a")];
+
+        let instruction = diff_chunk_paths(&from, &to).unwrap();
+
+        assert_eq!(instruction.added.len(), 0);
+        assert_eq!(instruction.changed.len(), 0);
+        assert_eq!(instruction.removed.len(), 1);
+        assert_eq!(instruction.removed[0].chunk_id, RcStr::from("b"));
+    }
+
+    #[test]
+    fn diff_orders_added_entries_after_their_dependency_order_predecessor() {
+        // `to` is already in dependency order: a before b before c.
+        let from = vec![entry("a.js", "a")];
+        let to = vec![entry("a.js", "a"), entry("b.js", "b"), entry("c.js", "c")];
+
+        let instruction = diff_chunk_paths(&from, &to).unwrap();
+
+        let added: HashMap<RcStr, Option<RcStr>> = instruction
+            .added
+            .into_iter()
+            .map(|update| (update.chunk_id, update.after))
+            .collect();
+        assert_eq!(added[&RcStr::from("b")], Some(RcStr::from("a")));
+        assert_eq!(added[&RcStr::from("c")], Some(RcStr::from("b")));
+    }
+
+    #[test]
+    fn diff_keeps_scripts_and_stylesheets_ordered_independently() {
+        let from = vec![];
+        let to = vec![
+            entry("a.js", "a"),
+            entry("a.css", "a-css"),
+            entry("b.js", "b"),
+            entry("b.css", "b-css"),
+        ];
+
+        let instruction = diff_chunk_paths(&from, &to).unwrap();
+
+        let added: HashMap<RcStr, Option<RcStr>> = instruction
+            .added
+            .into_iter()
+            .map(|update| (update.chunk_id, update.after))
+            .collect();
+        assert_eq!(added[&RcStr::from("a")], None);
+        assert_eq!(added[&RcStr::from("b")], Some(RcStr::from("a")));
+        assert_eq!(added[&RcStr::from("a-css")], None);
+        assert_eq!(added[&RcStr::from("b-css")], Some(RcStr::from("a-css")));
+    }
+
+    fn visit_all(
+        relative_paths: &HashMap<RcStr, RcStr>,
+        dependencies: &HashMap<RcStr, Vec<RcStr>>,
+    ) -> Vec<(RcStr, RcStr)> {
+        let mut chunk_ids: Vec<&RcStr> = relative_paths.keys().collect();
+        chunk_ids.sort_by_key(|chunk_id| &relative_paths[*chunk_id]);
+
+        let mut visited = HashSet::new();
+        let mut ordered = Vec::new();
+        for chunk_id in chunk_ids {
+            visit_chunk_in_dependency_order(
+                chunk_id,
+                relative_paths,
+                dependencies,
+                &mut visited,
+                &mut ordered,
+            );
+        }
+        ordered
+    }
+
+    #[test]
+    fn orders_dependencies_before_dependents() {
+        let relative_paths: HashMap<RcStr, RcStr> = [
+            ("a".into(), "a.js".into()),
+            ("b".into(), "b.js".into()),
+            ("c".into(), "c.js".into()),
+        ]
+        .into_iter()
+        .collect();
+        // c depends on b, b depends on a.
+        let dependencies: HashMap<RcStr, Vec<RcStr>> = [
+            ("c".into(), vec!["b".into()]),
+            ("b".into(), vec!["a".into()]),
+            ("a".into(), vec![]),
+        ]
+        .into_iter()
+        .collect();
+
+        let ordered = visit_all(&relative_paths, &dependencies);
+
+        assert_eq!(
+            ordered,
+            vec![entry("a.js", "a"), entry("b.js", "b"), entry("c.js", "c")]
+        );
+    }
+
+    #[test]
+    fn ties_break_by_relative_path() {
+        let relative_paths: HashMap<RcStr, RcStr> =
+            [("z".into(), "a.js".into()), ("y".into(), "b.js".into())]
+                .into_iter()
+                .collect();
+        let dependencies: HashMap<RcStr, Vec<RcStr>> = HashMap::new();
+
+        let ordered = visit_all(&relative_paths, &dependencies);
+
+        assert_eq!(ordered, vec![entry("a.js", "z"), entry("b.js", "y")]);
+    }
+
+    #[test]
+    fn cycle_visits_each_chunk_exactly_once() {
+        let relative_paths: HashMap<RcStr, RcStr> =
+            [("a".into(), "a.js".into()), ("b".into(), "b.js".into())]
+                .into_iter()
+                .collect();
+        // a and b depend on each other.
+        let dependencies: HashMap<RcStr, Vec<RcStr>> = [
+            ("a".into(), vec!["b".into()]),
+            ("b".into(), vec!["a".into()]),
+        ]
+        .into_iter()
+        .collect();
+
+        let ordered = visit_all(&relative_paths, &dependencies);
+
+        assert_eq!(ordered.len(), 2);
+        assert!(ordered.contains(&entry("a.js", "a")));
+        assert!(ordered.contains(&entry("b.js", "b")));
+    }
+
+    #[test]
+    fn dependency_outside_the_chunk_group_is_ignored() {
+        let relative_paths: HashMap<RcStr, RcStr> =
+            [("a".into(), "a.js".into())].into_iter().collect();
+        // a depends on a chunk that never made it into `relative_paths` (e.g. it
+        // resolved to an asset outside this chunk group).
+        let dependencies: HashMap<RcStr, Vec<RcStr>> = [("a".into(), vec!["missing".into()])]
+            .into_iter()
+            .collect();
+
+        let ordered = visit_all(&relative_paths, &dependencies);
+
+        assert_eq!(ordered, vec![entry("a.js", "a")]);
+    }
+}
@@ -1,22 +1,26 @@
 //! Static asset support for turbopack.
 //!
-//! Static assets are copied directly to the output folder.
+//! Static assets are copied directly to the output folder, unless they are
+//! no larger than the [`ChunkingContextVc`]'s inline size threshold, in which
+//! case they are embedded as a `data:` URI instead.
 //!
 //! When imported from ES modules, they produce a thin module that simply
-//! exports the asset's path.
+//! exports the asset's path (or its inlined data URI).
 //!
-//! When referred to from CSS assets, the reference is replaced with the asset's
-//! path.
+//! When referred to from CSS assets, the reference is replaced with the
+//! asset's path (or its inlined data URI).
 
 #![feature(min_specialization)]
 
 use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
 use turbo_tasks::{primitives::StringVc, ValueToString, ValueToStringVc};
 use turbo_tasks_fs::{FileContent, FileContentVc, FileSystemPathVc};
 use turbopack_core::{
     asset::{Asset, AssetVc},
     chunk::{ChunkItem, ChunkItemVc, ChunkVc, ChunkableAsset, ChunkableAssetVc, ChunkingContextVc},
     context::AssetContextVc,
+    rcstr::RcStr,
     reference::{AssetReferencesVc, SingleAssetReferenceVc},
 };
 use turbopack_css::embed::{CssEmbed, CssEmbedVc, CssEmbeddable, CssEmbeddableVc};
@@ -45,14 +49,11 @@ impl StaticModuleAssetVc {
     }
 
     #[turbo_tasks::function]
-    async fn static_asset(
+    async fn embeddable_asset(
         self_vc: StaticModuleAssetVc,
         context: ChunkingContextVc,
-    ) -> Result<StaticAssetVc> {
-        Ok(StaticAssetVc::cell(StaticAsset {
-            context,
-            source: self_vc.await?.source,
-        }))
+    ) -> Result<EmbeddableStaticAssetVc> {
+        Ok(EmbeddableStaticAssetVc::new(self_vc.await?.source, context))
     }
 }
 
@@ -90,7 +91,7 @@ impl EcmascriptChunkPlaceable for StaticModuleAsset {
         ModuleChunkItemVc::cell(ModuleChunkItem {
             module: self_vc,
             context,
-            static_asset: self_vc.static_asset(context),
+            embed: self_vc.embeddable_asset(context),
         })
         .into()
     }
@@ -106,7 +107,7 @@ impl CssEmbeddable for StaticModuleAsset {
     #[turbo_tasks::function]
     fn as_css_embed(self_vc: StaticModuleAssetVc, context: ChunkingContextVc) -> CssEmbedVc {
         StaticCssEmbedVc::cell(StaticCssEmbed {
-            static_asset: self_vc.static_asset(context),
+            embed: self_vc.embeddable_asset(context),
         })
         .into()
     }
@@ -158,22 +159,138 @@ impl Asset for StaticAsset {
     }
 }
 
+/// An asset small enough to be inlined as a `data:` URI, at or below the
+/// [`ChunkingContextVc`]'s configured size threshold. Never written to the
+/// output folder, so embedding it never costs an extra network request.
+///
+/// `path()` is only an identity placeholder for the [`Asset`] trait: it is
+/// never a real location on disk, since this asset is never written to the
+/// output folder. Callers that need the actual value to embed must use
+/// [`EmbeddableStaticAssetVc::exported_value`] rather than formatting
+/// `path()` as if it were a root-relative output path.
+#[turbo_tasks::value]
+struct InlineAsset {
+    context: ChunkingContextVc,
+    source: AssetVc,
+    data_uri: RcStr,
+    /// A stable identity for `path()`, since the `data:` URI itself can run
+    /// to megabytes and contains characters (`/`, `+`, `=`) that make for a
+    /// fragile pseudo-path.
+    content_hash_b16: RcStr,
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for InlineAsset {
+    #[turbo_tasks::function]
+    fn path(&self) -> FileSystemPathVc {
+        self.context
+            .asset_path(&format!("{}.inline", self.content_hash_b16))
+    }
+
+    #[turbo_tasks::function]
+    fn content(&self) -> FileContentVc {
+        self.source.content()
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> AssetReferencesVc {
+        AssetReferencesVc::empty()
+    }
+}
+
+/// Either a real file written to the output folder and referenced by its
+/// hashed path, or a small asset inlined as a `data:` URI with no extra
+/// network request.
+#[turbo_tasks::value]
+enum EmbeddableStaticAsset {
+    External(StaticAssetVc),
+    Inline(InlineAssetVc),
+}
+
+#[turbo_tasks::value_impl]
+impl EmbeddableStaticAssetVc {
+    #[turbo_tasks::function]
+    async fn new(source: AssetVc, context: ChunkingContextVc) -> Result<Self> {
+        let content = source.content().await?;
+        let bytes = match &*content {
+            FileContent::Content(file) => file.content(),
+            _ => return Err(anyhow!("StaticAsset: unsupported file content")),
+        };
+
+        // `asset_inline_limit` is a new method this change adds to the
+        // `ChunkingContext` trait (defined in `turbopack-core`, outside this
+        // crate) so the threshold is configurable per chunking context instead
+        // of hardcoded here; contexts that don't override it fall back to `0`
+        // (never inline).
+        let inline_limit = *context.asset_inline_limit().await? as usize;
+        if !fits_inline_limit(bytes.len(), inline_limit) {
+            return Ok(EmbeddableStaticAsset::External(StaticAssetVc::cell(StaticAsset {
+                context,
+                source,
+            }))
+            .cell());
+        }
+
+        let mime = mime_for_extension(source.path().await?.extension());
+        let data_uri: RcStr = format!("data:{};base64,{}", mime, STANDARD.encode(bytes)).into();
+        let content_hash_b16: RcStr =
+            turbopack_hash::encode_base16(&turbopack_hash::hash_md4(bytes)).into();
+
+        Ok(EmbeddableStaticAsset::Inline(InlineAssetVc::cell(InlineAsset {
+            context,
+            source,
+            data_uri,
+            content_hash_b16,
+        }))
+        .cell())
+    }
+
+    #[turbo_tasks::function]
+    async fn as_asset(self) -> Result<AssetVc> {
+        Ok(match &*self.await? {
+            EmbeddableStaticAsset::External(asset) => asset.as_asset(),
+            EmbeddableStaticAsset::Inline(asset) => asset.as_asset(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self) -> Result<AssetReferencesVc> {
+        Ok(match &*self.await? {
+            EmbeddableStaticAsset::External(asset) => {
+                AssetReferencesVc::cell(vec![SingleAssetReferenceVc::new(
+                    (*asset).into(),
+                    StringVc::cell(format!("static(url) {}", asset.path().await?)),
+                )
+                .into()])
+            }
+            EmbeddableStaticAsset::Inline(_) => AssetReferencesVc::empty(),
+        })
+    }
+
+    /// The value that should be embedded wherever this asset is referenced:
+    /// the root-relative output path for an externally-written asset, or the
+    /// literal `data:` URI for an inlined one.
+    #[turbo_tasks::function]
+    async fn exported_value(self) -> Result<StringVc> {
+        Ok(StringVc::cell(match &*self.await? {
+            EmbeddableStaticAsset::External(asset) => format!("/{}", asset.path().await?),
+            EmbeddableStaticAsset::Inline(asset) => asset.await?.data_uri.to_string(),
+        }))
+    }
+}
+
 #[turbo_tasks::value]
 struct ModuleChunkItem {
     module: StaticModuleAssetVc,
     context: ChunkingContextVc,
-    static_asset: StaticAssetVc,
+    embed: EmbeddableStaticAssetVc,
 }
 
 #[turbo_tasks::value_impl]
 impl ChunkItem for ModuleChunkItem {
     #[turbo_tasks::function]
     async fn references(&self) -> Result<AssetReferencesVc> {
-        Ok(AssetReferencesVc::cell(vec![SingleAssetReferenceVc::new(
-            self.static_asset.into(),
-            StringVc::cell(format!("static(url) {}", self.static_asset.path().await?)),
-        )
-        .into()]))
+        Ok(self.embed.references())
     }
 }
 
@@ -187,8 +304,8 @@ impl EcmascriptChunkItem for ModuleChunkItem {
     ) -> Result<EcmascriptChunkItemContentVc> {
         Ok(EcmascriptChunkItemContent {
             inner_code: format!(
-                "__turbopack_export_value__({path});",
-                path = stringify_str(&format!("/{}", &*self.static_asset.path().await?))
+                "__turbopack_export_value__({value});",
+                value = stringify_str(&*self.embed.exported_value().await?)
             ),
             id: chunk_context.id(EcmascriptChunkPlaceableVc::cast_from(self.module)),
             options: EcmascriptChunkItemOptions {
@@ -201,26 +318,39 @@ impl EcmascriptChunkItem for ModuleChunkItem {
 
 #[turbo_tasks::value]
 struct StaticCssEmbed {
-    static_asset: StaticAssetVc,
+    embed: EmbeddableStaticAssetVc,
 }
 
 #[turbo_tasks::value_impl]
 impl CssEmbed for StaticCssEmbed {
     #[turbo_tasks::function]
     async fn references(&self) -> Result<AssetReferencesVc> {
-        Ok(AssetReferencesVc::cell(vec![SingleAssetReferenceVc::new(
-            self.static_asset.into(),
-            StringVc::cell(format!("static(url) {}", self.static_asset.path().await?)),
-        )
-        .into()]))
+        Ok(self.embed.references())
     }
 
     #[turbo_tasks::function]
     fn embeddable_asset(&self) -> AssetVc {
-        self.static_asset.as_asset()
+        self.embed.as_asset()
     }
 }
 
+/// Whether an asset of `byte_len` bytes should be inlined as a `data:` URI
+/// rather than written to the output folder, given the chunking context's
+/// `inline_limit`. `0` means "never inline": without this special case,
+/// `byte_len > inline_limit` is false for an empty asset even at the
+/// never-inline default, which would inline it anyway.
+fn fits_inline_limit(byte_len: usize, inline_limit: usize) -> bool {
+    inline_limit != 0 && byte_len <= inline_limit
+}
+
+/// The MIME type to embed an asset's `data:` URI under, falling back to
+/// `application/octet-stream` when the asset has no extension (or one
+/// `mime_guess` doesn't recognize) to infer one from.
+fn mime_for_extension(ext: Option<&str>) -> mime_guess::Mime {
+    ext.and_then(|ext| mime_guess::from_ext(ext).first())
+        .unwrap_or(mime_guess::mime::APPLICATION_OCTET_STREAM)
+}
+
 pub fn register() {
     turbo_tasks::register();
     turbo_tasks_fs::register();
@@ -228,3 +358,38 @@ pub fn register() {
     turbopack_ecmascript::register();
     include!(concat!(env!("OUT_DIR"), "/register.rs"));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_limit_never_inlines_even_an_empty_asset() {
+        assert!(!fits_inline_limit(0, 0));
+        assert!(!fits_inline_limit(10, 0));
+    }
+
+    #[test]
+    fn inlines_up_to_and_including_the_limit() {
+        assert!(fits_inline_limit(0, 10));
+        assert!(fits_inline_limit(10, 10));
+        assert!(!fits_inline_limit(11, 10));
+    }
+
+    #[test]
+    fn mime_is_inferred_from_extension() {
+        assert_eq!(mime_for_extension(Some("png")), mime_guess::mime::IMAGE_PNG);
+    }
+
+    #[test]
+    fn mime_falls_back_to_octet_stream_without_a_recognized_extension() {
+        assert_eq!(
+            mime_for_extension(None),
+            mime_guess::mime::APPLICATION_OCTET_STREAM
+        );
+        assert_eq!(
+            mime_for_extension(Some("not-a-real-extension")),
+            mime_guess::mime::APPLICATION_OCTET_STREAM
+        );
+    }
+}